@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// User-facing configuration loaded from `~/.config/trayer/config.toml`.
+///
+/// Falls back to [`Config::default`] whenever the file is missing or fails
+/// to parse; callers should not treat either case as fatal.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub window: WindowConfig,
+    pub tray: TrayConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub size: (f32, f32),
+    pub position: WindowPosition,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            size: (400.0, 300.0),
+            position: WindowPosition::Centered,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowPosition {
+    #[default]
+    Centered,
+    Default,
+}
+
+impl From<WindowPosition> for iced::window::Position {
+    fn from(position: WindowPosition) -> Self {
+        match position {
+            WindowPosition::Centered => iced::window::Position::Centered,
+            WindowPosition::Default => iced::window::Position::Default,
+        }
+    }
+}
+
+impl WindowConfig {
+    pub fn to_settings(&self) -> iced::window::Settings {
+        iced::window::Settings {
+            size: iced::Size::new(self.size.0, self.size.1),
+            position: self.position.into(),
+            ..iced::window::Settings::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TrayConfig {
+    pub icon_path: Option<PathBuf>,
+    pub title: String,
+    pub menu: Vec<MenuEntry>,
+}
+
+impl Default for TrayConfig {
+    fn default() -> Self {
+        Self {
+            icon_path: None,
+            title: "Trayer".into(),
+            menu: vec![
+                MenuEntry {
+                    label: "Show Window".into(),
+                    action: MenuAction::Show,
+                },
+                MenuEntry {
+                    label: "Hide Window".into(),
+                    action: MenuAction::Hide,
+                },
+                MenuEntry {
+                    label: "Exit".into(),
+                    action: MenuAction::Exit,
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MenuEntry {
+    pub label: String,
+    pub action: MenuAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MenuAction {
+    Show,
+    Hide,
+    Exit,
+}
+
+impl Config {
+    /// Reads `config.toml` from the XDG config directory, falling back to
+    /// built-in defaults when the file is absent or malformed. Never panics.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            tracing::warn!("no config directory available, using default config");
+            return Self::default();
+        };
+
+        Self::load_from(&path)
+    }
+
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("trayer").join("config.toml"))
+    }
+
+    fn load_from(path: &Path) -> Self {
+        let raw = match std::fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tracing::debug!(path = %path.display(), "no config file found, using defaults");
+                return Self::default();
+            }
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "failed to read config file, using defaults");
+                return Self::default();
+            }
+        };
+
+        match toml::from_str(&raw) {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "failed to parse config file, using defaults");
+                Self::default()
+            }
+        }
+    }
+}
@@ -2,39 +2,110 @@ use iced::widget::{button, column, container, text};
 use iced::window;
 use iced::{Element, Subscription, Task};
 use image::GenericImageView;
-use std::sync::LazyLock;
-use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::mpsc;
 
 use ksni::TrayMethods;
+use tracing::{debug, info};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
 
-static TRAY_COMMANDS: LazyLock<Arc<Mutex<Vec<TrayCommand>>>> =
-    LazyLock::new(|| Arc::new(Mutex::new(Vec::new())));
+mod config;
+
+use config::{Config, MenuAction};
 
 #[derive(Debug, Clone)]
 enum TrayCommand {
-    ShowWindow,
-    HideWindow,
+    ShowWindow { label: String },
+    FocusWindow(window::Id),
+    CloseWindow(window::Id),
     Exit,
 }
 
+/// A window the app currently has open, as tracked for the tray's
+/// per-window submenu.
+struct WindowState {
+    label: String,
+}
+
+/// Snapshot of application state mirrored into the tray so its menu and
+/// tooltip stay in sync with the GUI.
+#[derive(Debug, Clone, Default)]
+struct TrayState {
+    counter: i32,
+    windows: Vec<(window::Id, String)>,
+    active_window: Option<window::Id>,
+    next_window_index: usize,
+}
+
+/// Initializes the `tracing` subscriber with a stderr layer and a non-blocking
+/// rolling file appender under the XDG state directory. The returned guard
+/// must be kept alive for the process lifetime so buffered logs are flushed.
+fn init_logging() -> tracing_appender::non_blocking::WorkerGuard {
+    let log_dir = dirs::state_dir()
+        .or_else(dirs::cache_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("trayer");
+    let file_appender = tracing_appender::rolling::daily(&log_dir, "trayer.log");
+    let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+
+    let file_layer = tracing_subscriber::fmt::layer()
+        .with_writer(non_blocking)
+        .with_ansi(false);
+    let stderr_layer = tracing_subscriber::fmt::layer().with_writer(std::io::stderr);
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(file_layer)
+        .with(stderr_layer)
+        .init();
+
+    info!(dir = %log_dir.display(), "logging initialized");
+    guard
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let _log_guard = init_logging();
+
+    let config = Config::load();
+
     let rt = tokio::runtime::Runtime::new()?;
     let _guard = rt.enter();
-    std::thread::spawn(|| {
+
+    let (tray_tx, tray_rx) = mpsc::unbounded_channel::<TrayCommand>();
+    let tray_rx = Arc::new(Mutex::new(Some(tray_rx)));
+
+    let tray_handle: Arc<Mutex<Option<ksni::Handle<SystemTray>>>> = Arc::new(Mutex::new(None));
+    let tray_handle_for_thread = tray_handle.clone();
+
+    let tray_config = config.tray.clone();
+    std::thread::spawn(move || {
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
-            let tray = SystemTray;
-            if let Err(e) = tray.spawn().await {
-                println!("failed to spawn tray {e}");
-            } else {
-                std::future::pending::<()>().await;
+            let tray = SystemTray::new(tray_tx, tray_config);
+            match tray.spawn().await {
+                Ok(handle) => {
+                    debug!("tray spawned");
+                    *tray_handle_for_thread
+                        .lock()
+                        .expect("tray handle mutex poisoned") = Some(handle);
+                    std::future::pending::<()>().await;
+                }
+                Err(e) => tracing::error!(error = %e, "failed to spawn tray"),
             }
         });
     });
 
-    let result = iced::daemon(Example::new, Example::update, Example::view)
-        .subscription(Example::subscription)
-        .run();
+    let window_config = config.window.clone();
+    let result = iced::daemon(
+        |_state: &Example, _window| "Trayer".to_string(),
+        Example::update,
+        Example::view,
+    )
+    .subscription(Example::subscription)
+    .run_with(move || Example::new(tray_rx.clone(), tray_handle.clone(), window_config.clone()));
 
     match result {
         Ok(_) => Ok(()),
@@ -44,105 +115,152 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 struct Example {
     counter: i32,
-    current_window_id: Option<window::Id>,
-    window_is_open: bool,
+    windows: HashMap<window::Id, WindowState>,
+    active_window: Option<window::Id>,
+    next_window_index: usize,
+    tray_rx: Arc<Mutex<Option<mpsc::UnboundedReceiver<TrayCommand>>>>,
+    tray_handle: Arc<Mutex<Option<ksni::Handle<SystemTray>>>>,
+    window_config: config::WindowConfig,
 }
 
 #[derive(Debug, Clone)]
 enum Message {
-    WindowOpened(window::Id),
+    WindowOpened(window::Id, String),
     WindowClosed(window::Id),
-    HideToTray,
+    CloseWindow(window::Id),
     ExitApp,
     Increment,
     Decrement,
-    CheckTrayCommands,
+    TrayCommand(TrayCommand),
+    TraySynced,
 }
 
 impl Example {
-    fn new() -> (Self, Task<Message>) {
-        let (_id, open) = window::open(window::Settings {
-            size: iced::Size::new(400.0, 300.0),
-            position: window::Position::Centered,
-            ..window::Settings::default()
-        });
+    fn new(
+        tray_rx: Arc<Mutex<Option<mpsc::UnboundedReceiver<TrayCommand>>>>,
+        tray_handle: Arc<Mutex<Option<ksni::Handle<SystemTray>>>>,
+        window_config: config::WindowConfig,
+    ) -> (Self, Task<Message>) {
+        let mut example = Self {
+            counter: 0,
+            windows: HashMap::new(),
+            active_window: None,
+            next_window_index: 1,
+            tray_rx,
+            tray_handle,
+            window_config,
+        };
 
-        (
-            Self {
-                counter: 0,
-                current_window_id: None,
-                window_is_open: false,
-            },
-            open.map(Message::WindowOpened),
-        )
+        let task = example.open_window();
+        (example, task)
+    }
+
+    /// Opens a new window, labelled for display in the tray's "Windows"
+    /// submenu.
+    fn open_window(&mut self) -> Task<Message> {
+        let label = format!("Window {}", self.next_window_index);
+        self.open_window_labeled(label)
+    }
+
+    fn open_window_labeled(&mut self, label: String) -> Task<Message> {
+        self.next_window_index += 1;
+
+        let (_id, open) = window::open(self.window_config.to_settings());
+        open.map(move |id| Message::WindowOpened(id, label.clone()))
+    }
+
+    /// Pushes the current counter/window state into the tray so its menu
+    /// and tooltip reflect the GUI. A no-op if the tray hasn't spawned yet.
+    fn sync_tray(&self) -> Task<Message> {
+        let Some(handle) = self
+            .tray_handle
+            .lock()
+            .expect("tray handle mutex poisoned")
+            .clone()
+        else {
+            return Task::none();
+        };
+
+        let state = TrayState {
+            counter: self.counter,
+            windows: self
+                .windows
+                .iter()
+                .map(|(id, window)| (*id, window.label.clone()))
+                .collect(),
+            active_window: self.active_window,
+            next_window_index: self.next_window_index,
+        };
+
+        Task::future(async move {
+            handle.update(move |tray| tray.state = state).await;
+            Message::TraySynced
+        })
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
         match message {
-            Message::WindowOpened(id) => {
-                self.current_window_id = Some(id);
-                self.window_is_open = true;
-                Task::none()
+            Message::WindowOpened(id, label) => {
+                info!(?id, %label, "window opened");
+                self.windows.insert(id, WindowState { label });
+                self.active_window = Some(id);
+                self.sync_tray()
             }
-            Message::WindowClosed(_id) => {
-                self.current_window_id = None;
-                self.window_is_open = false;
-                Task::none()
-            }
-            Message::HideToTray => {
-                if let Some(window_id) = self.current_window_id {
-                    window::close(window_id)
-                } else {
-                    Task::none()
+            Message::WindowClosed(id) => {
+                info!(?id, "window closed");
+                self.windows.remove(&id);
+                if self.active_window == Some(id) {
+                    self.active_window = self.windows.keys().next().copied();
                 }
+                self.sync_tray()
             }
+            Message::CloseWindow(id) => window::close(id),
             Message::ExitApp => iced::exit(),
             Message::Increment => {
                 self.counter += 1;
-                Task::none()
+                self.sync_tray()
             }
             Message::Decrement => {
                 self.counter -= 1;
-                Task::none()
+                self.sync_tray()
             }
-            Message::CheckTrayCommands => {
-                if let Ok(mut commands) = TRAY_COMMANDS.lock() {
-                    if let Some(cmd) = commands.pop() {
-                        match cmd {
-                            TrayCommand::ShowWindow => {
-                                if !self.window_is_open {
-                                    let (_id, open) = window::open(window::Settings {
-                                        size: iced::Size::new(400.0, 300.0),
-                                        position: window::Position::Centered,
-                                        ..window::Settings::default()
-                                    });
-                                    return open.map(Message::WindowOpened);
-                                }
-                            }
-                            TrayCommand::HideWindow => {
-                                if let Some(window_id) = self.current_window_id {
-                                    return window::close(window_id);
-                                }
-                            }
-                            TrayCommand::Exit => {
-                                return iced::exit();
-                            }
+            Message::TrayCommand(cmd) => {
+                debug!(?cmd, "tray command received");
+                match cmd {
+                    TrayCommand::ShowWindow { label } => self.open_window_labeled(label),
+                    TrayCommand::FocusWindow(id) => {
+                        if self.windows.contains_key(&id) {
+                            self.active_window = Some(id);
+                            window::gain_focus(id)
+                        } else {
+                            Task::none()
                         }
                     }
+                    TrayCommand::CloseWindow(id) => window::close(id),
+                    TrayCommand::Exit => {
+                        info!("exiting application");
+                        iced::exit()
+                    }
                 }
-                Task::none()
             }
+            Message::TraySynced => Task::none(),
         }
     }
 
-    fn view(&self, _window_id: window::Id) -> Element<'_, Message> {
+    fn view(&self, window_id: window::Id) -> Element<'_, Message> {
+        let label = self
+            .windows
+            .get(&window_id)
+            .map(|window| window.label.as_str())
+            .unwrap_or("Trayer Application");
+
         let content = column![
-            text("Trayer Application").size(24),
+            text(label).size(24),
             text(format!("Counter: {}", self.counter)).size(18),
             text("Simple system tray app").size(16),
             button("Increment").on_press(Message::Increment),
             button("Decrement").on_press(Message::Decrement),
-            button("Hide to Tray").on_press(Message::HideToTray),
+            button("Hide to Tray").on_press(Message::CloseWindow(window_id)),
             button("Exit").on_press(Message::ExitApp),
         ]
         .spacing(15)
@@ -157,16 +275,38 @@ impl Example {
     }
 
     fn subscription(&self) -> Subscription<Message> {
+        let tray_rx = self.tray_rx.clone();
+
         iced::Subscription::batch([
             window::close_events().map(Message::WindowClosed),
-            iced::time::every(std::time::Duration::from_millis(500))
-                .map(|_| Message::CheckTrayCommands),
+            Subscription::run_with_id(
+                "tray-commands",
+                iced::stream::channel(1, move |mut output| async move {
+                    use iced::futures::sink::SinkExt;
+
+                    let mut rx = tray_rx
+                        .lock()
+                        .expect("tray receiver mutex poisoned")
+                        .take()
+                        .expect("tray command subscription started twice");
+
+                    while let Some(command) = rx.recv().await {
+                        if output.send(Message::TrayCommand(command)).await.is_err() {
+                            break;
+                        }
+                    }
+                }),
+            ),
         ])
     }
 }
 
 //tray related boilerplate
-struct SystemTray;
+struct SystemTray {
+    commands: mpsc::UnboundedSender<TrayCommand>,
+    config: config::TrayConfig,
+    state: TrayState,
+}
 
 impl ksni::Tray for SystemTray {
     fn id(&self) -> String {
@@ -174,79 +314,163 @@ impl ksni::Tray for SystemTray {
     }
 
     fn title(&self) -> String {
-        "Trayer".into()
+        self.config.title.clone()
     }
 
     fn icon_pixmap(&self) -> Vec<ksni::Icon> {
-        static ICON: LazyLock<ksni::Icon> = LazyLock::new(|| {
-            let img = image::load_from_memory_with_format(
-                include_bytes!("../icons/custom_icon.png"),
-                image::ImageFormat::Png,
-            )
-            .expect("valid image");
-            let (width, height) = img.dimensions();
-            let mut data = img.into_rgba8().into_vec();
-            assert_eq!(data.len() % 4, 0);
-            for pixel in data.chunks_exact_mut(4) {
-                pixel.rotate_right(1) // rgba to argb
-            }
-            ksni::Icon {
-                width: width as i32,
-                height: height as i32,
-                data,
-            }
-        });
+        static DEFAULT_ICON: LazyLock<ksni::Icon> =
+            LazyLock::new(|| load_icon(include_bytes!("../icons/custom_icon.png")));
 
-        vec![ICON.clone()]
+        match &self.config.icon_path {
+            Some(path) => match std::fs::read(path) {
+                Ok(bytes) => vec![load_icon(&bytes)],
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "failed to read configured icon, using default");
+                    vec![DEFAULT_ICON.clone()]
+                }
+            },
+            None => vec![DEFAULT_ICON.clone()],
+        }
     }
 
     fn icon_name(&self) -> String {
         "application-default-icon".into()
     }
 
+    fn tool_tip(&self) -> ksni::ToolTip {
+        ksni::ToolTip {
+            title: format!("Counter: {}", self.state.counter),
+            ..Default::default()
+        }
+    }
+
     fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
         use ksni::menu::*;
 
-        vec![
-            StandardItem {
-                label: "Show Window".into(),
-                activate: Box::new(|_this: &mut Self| {
-                    Self::send_command(TrayCommand::ShowWindow);
-                }),
-                ..Default::default()
-            }
-            .into(),
-            StandardItem {
-                label: "Hide Window".into(),
-                activate: Box::new(|_this: &mut Self| {
-                    Self::send_command(TrayCommand::HideWindow);
-                }),
-                ..Default::default()
-            }
-            .into(),
-            ksni::MenuItem::Separator,
-            StandardItem {
-                label: "Exit".into(),
-                icon_name: "application-exit".into(),
-                activate: Box::new(|_this: &mut Self| {
-                    Self::send_command(TrayCommand::Exit);
-                }),
+        let has_windows = !self.state.windows.is_empty();
+
+        let mut items: Vec<ksni::MenuItem<Self>> = self
+            .config
+            .menu
+            .iter()
+            .map(|entry| {
+                let enabled = match entry.action {
+                    MenuAction::Show => true,
+                    MenuAction::Hide => has_windows,
+                    MenuAction::Exit => true,
+                };
+                let action = entry.action;
+                StandardItem {
+                    label: entry.label.clone(),
+                    icon_name: match entry.action {
+                        MenuAction::Exit => "application-exit".into(),
+                        _ => String::new(),
+                    },
+                    enabled,
+                    activate: Box::new(move |this: &mut Self| match action {
+                        MenuAction::Show => this.send_command(TrayCommand::ShowWindow {
+                            label: format!("Window {}", this.state.next_window_index),
+                        }),
+                        MenuAction::Hide => {
+                            if let Some(id) = this.state.active_window {
+                                this.send_command(TrayCommand::CloseWindow(id));
+                            }
+                        }
+                        MenuAction::Exit => this.send_command(TrayCommand::Exit),
+                    }),
+                    ..Default::default()
+                }
+                .into()
+            })
+            .collect();
+
+        let windows_submenu: Vec<ksni::MenuItem<Self>> = self
+            .state
+            .windows
+            .iter()
+            .map(|(id, label)| {
+                let id = *id;
+                SubMenu {
+                    label: label.clone(),
+                    submenu: vec![
+                        StandardItem {
+                            label: "Focus".into(),
+                            activate: Box::new(move |this: &mut Self| {
+                                this.send_command(TrayCommand::FocusWindow(id));
+                            }),
+                            ..Default::default()
+                        }
+                        .into(),
+                        StandardItem {
+                            label: "Close".into(),
+                            icon_name: "window-close".into(),
+                            activate: Box::new(move |this: &mut Self| {
+                                this.send_command(TrayCommand::CloseWindow(id));
+                            }),
+                            ..Default::default()
+                        }
+                        .into(),
+                    ],
+                    ..Default::default()
+                }
+                .into()
+            })
+            .collect();
+
+        items.push(MenuItem::Separator);
+        items.push(
+            SubMenu {
+                label: "Windows".into(),
+                enabled: has_windows,
+                submenu: windows_submenu,
                 ..Default::default()
             }
             .into(),
-        ]
+        );
+
+        items
     }
 
     fn activate(&mut self, _x: i32, _y: i32) {
-        // Show window on tray click
-        Self::send_command(TrayCommand::ShowWindow);
+        // Focus the existing window on tray click; only open a new one if none are open.
+        if let Some(id) = self.state.active_window {
+            self.send_command(TrayCommand::FocusWindow(id));
+        } else {
+            self.send_command(TrayCommand::ShowWindow {
+                label: format!("Window {}", self.state.next_window_index),
+            });
+        }
     }
 }
 
 impl SystemTray {
-    fn send_command(cmd: TrayCommand) {
-        if let Ok(mut commands) = TRAY_COMMANDS.lock() {
-            commands.push(cmd);
+    fn new(commands: mpsc::UnboundedSender<TrayCommand>, config: config::TrayConfig) -> Self {
+        debug!("system tray created");
+        Self {
+            commands,
+            config,
+            state: TrayState::default(),
         }
     }
+
+    fn send_command(&self, cmd: TrayCommand) {
+        debug!(?cmd, "sending tray command");
+        let _ = self.commands.send(cmd);
+    }
+}
+
+fn load_icon(bytes: &[u8]) -> ksni::Icon {
+    let img =
+        image::load_from_memory_with_format(bytes, image::ImageFormat::Png).expect("valid image");
+    let (width, height) = img.dimensions();
+    let mut data = img.into_rgba8().into_vec();
+    assert_eq!(data.len() % 4, 0);
+    for pixel in data.chunks_exact_mut(4) {
+        pixel.rotate_right(1) // rgba to argb
+    }
+    ksni::Icon {
+        width: width as i32,
+        height: height as i32,
+        data,
+    }
 }